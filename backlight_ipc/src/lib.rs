@@ -7,28 +7,51 @@ use serde::{Deserialize, Serialize};
 
 pub const DEFAULT_UNIX_SOCKET_PATH: &str = "/run/backlightd.sock";
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum BacklightMode {
     Auto,
     Manual,
+    /// Brightness is driven by an ambient light sensor instead of the clock/location curve.
+    Adaptive,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Selects which monitor(s) a command applies to. `Monitor` matches on `BacklightDevice::name()`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum BacklightTarget {
+    All,
+    Monitor(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    pub name: String,
+    pub brightness_percent: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct BacklightInfo {
     pub brightness_percent: u8,
+    pub monitors: Vec<MonitorInfo>,
+    pub mode: BacklightMode,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum BacklightCommand {
-    SetBrightness(u8),
-    IncreaseBrightness(u8),
-    DecreaseBrightness(u8),
-    TurnOffMonitors,
-    TurnOnMonitors,
+    SetBrightness(u8, BacklightTarget),
+    IncreaseBrightness(u8, BacklightTarget),
+    DecreaseBrightness(u8, BacklightTarget),
+    TurnOffMonitors(BacklightTarget),
+    TurnOnMonitors(BacklightTarget),
+    /// Never let the targeted monitor(s) go below this brightness percent, including from the
+    /// auto-adjust/adaptive paths.
+    SetMinimumBrightness(u8, BacklightTarget),
     Refresh,
     SetMode(BacklightMode),
     GetInfo,
     GetInfoResponse(BacklightInfo),
+    /// Hanging-get: keeps the connection open and asks the daemon to push a `GetInfoResponse`
+    /// every time brightness or mode changes, instead of the client having to poll `GetInfo`.
+    Watch,
     NotifyShutdown,
 }
 
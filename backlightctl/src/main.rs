@@ -1,6 +1,6 @@
 use std::{os::unix::net::UnixStream, path::PathBuf, process::exit};
 
-use backlight_ipc::{BacklightCommand, BacklightMode, DEFAULT_UNIX_SOCKET_PATH};
+use backlight_ipc::{BacklightCommand, BacklightMode, BacklightTarget, DEFAULT_UNIX_SOCKET_PATH};
 use clap::{error::ErrorKind, CommandFactory, Parser};
 
 #[derive(Parser)]
@@ -19,6 +19,11 @@ struct BacklightctlCli {
     #[clap(short, long, default_value_t = false)]
     refresh: bool,
 
+    /// Only apply --brightness to the monitor with this name (see `--json` output for names),
+    /// instead of every known monitor
+    #[clap(short, long)]
+    monitor: Option<String>,
+
     /// UNIX socket path (for test purposes)
     #[clap(short, long, default_value = DEFAULT_UNIX_SOCKET_PATH)]
     unix_socket_path: PathBuf,
@@ -40,6 +45,11 @@ fn main() {
             .exit();
     }
 
+    let target = match cli.monitor {
+        Some(name) => BacklightTarget::Monitor(name),
+        None => BacklightTarget::All,
+    };
+
     let brightness_cmd = if let Some(brightness) = cli.brightness {
         if brightness.chars().last().is_some_and(|c| c != '%') {
             BacklightctlCli::command()
@@ -80,7 +90,7 @@ fn main() {
                     .exit();
             }
 
-            Some(BacklightCommand::IncreaseBrightness(brightness))
+            Some(BacklightCommand::IncreaseBrightness(brightness, target.clone()))
         } else if potential_brightness_modifier.is_some_and(|c| c == '-') {
             let brightness = brightness
                 .chars()
@@ -109,7 +119,7 @@ fn main() {
                     .exit();
             }
 
-            Some(BacklightCommand::DecreaseBrightness(brightness as u8))
+            Some(BacklightCommand::DecreaseBrightness(brightness as u8, target.clone()))
         } else {
             let brightness = brightness
                 .chars()
@@ -137,7 +147,7 @@ fn main() {
                     .exit();
             }
 
-            Some(BacklightCommand::SetBrightness(brightness as u8))
+            Some(BacklightCommand::SetBrightness(brightness as u8, target.clone()))
         }
     } else {
         None
@@ -2,15 +2,52 @@ use std::{fs, path::PathBuf};
 
 use anyhow::bail;
 
-use crate::monitors::BacklightDevice;
+use crate::monitors::{BacklightDevice, FADE_STEPS};
 
 pub(crate) const ACPI_DEVICES_PATH: &str = "/sys/class/backlight";
 
+/// The floor enforced when no explicit minimum has been configured for a device: never go fully
+/// black, but otherwise leave as much range as possible to the user/auto-adjust logic.
+const DEFAULT_MINIMUM_BRIGHTNESS_PERCENT: u8 = 1;
+
+/// Human brightness perception is roughly logarithmic, so a linear percent-to-raw mapping feels
+/// cramped at the low end and barely moves at the high end. We instead map through `raw = max *
+/// (percent/100)^GAMMA`, and its inverse when reporting the percent back. 2.2 is the usual sRGB
+/// gamma and a reasonable default for a backlight curve.
+const GAMMA: f64 = 2.2;
+
+/// The minimum raw value we'll ever write, as a fraction of `max_brightness_raw`, so that "1%"
+/// stays dimly visible instead of going fully black on panels with a large raw range.
+const MINIMUM_RAW_FRACTION: f64 = 0.01;
+
+/// Converts a user-facing percent to a raw value through the gamma curve, then clamps it above
+/// the minimum raw floor.
+fn percent_to_raw(percent: u8, max_brightness_raw: u16) -> u16 {
+    let normalized = (percent as f64 / 100.).powf(GAMMA);
+    let raw = (normalized * max_brightness_raw as f64).round() as u16;
+    raw.max(minimum_raw(max_brightness_raw))
+}
+
+/// The inverse of `percent_to_raw`, used to report the current percent back to the user.
+fn raw_to_percent(raw: u16, max_brightness_raw: u16) -> u8 {
+    let normalized = raw as f64 / max_brightness_raw as f64;
+    (normalized.powf(1. / GAMMA) * 100.).round().clamp(0., 100.) as u8
+}
+
+fn minimum_raw(max_brightness_raw: u16) -> u16 {
+    ((max_brightness_raw as f64 * MINIMUM_RAW_FRACTION).ceil() as u16).max(1)
+}
+
 pub(crate) struct BacklightAcpiDevice {
     path: PathBuf,
     max_brightness_raw: u16,
     current_brightness_raw: u16,
     current_brightness_percent: u8,
+    minimum_brightness_percent: u8,
+    /// The raw value an in-progress fade started from and is heading towards, (re)computed on
+    /// the fade's first step. Meaningless once the fade completes or a new one starts.
+    fade_start_raw: u16,
+    fade_target_raw: u16,
 }
 
 impl BacklightAcpiDevice {
@@ -36,31 +73,110 @@ impl BacklightAcpiDevice {
             path,
             max_brightness_raw,
             current_brightness_raw,
-            current_brightness_percent: (current_brightness_raw * 100 / max_brightness_raw) as u8,
+            current_brightness_percent: raw_to_percent(current_brightness_raw, max_brightness_raw),
+            minimum_brightness_percent: DEFAULT_MINIMUM_BRIGHTNESS_PERCENT,
+            fade_start_raw: current_brightness_raw,
+            fade_target_raw: current_brightness_raw,
         })
     }
 }
 
+impl BacklightAcpiDevice {
+    fn write_raw(&mut self, raw: u16) -> anyhow::Result<()> {
+        let current_brightness_path = self.path.join("brightness");
+
+        if let Err(err) = fs::write(&current_brightness_path, raw.to_string()) {
+            bail!("{}: {err}", current_brightness_path.display());
+        }
+
+        self.current_brightness_raw = raw;
+        Ok(())
+    }
+}
+
 impl BacklightDevice for BacklightAcpiDevice {
     fn set_brightness(&mut self, percent: u8) -> anyhow::Result<()> {
         assert!(percent <= 100);
 
-        let current_brightness_path = self.path.join("brightness");
-        let new_brightness = (percent as f64 / 100. * self.max_brightness_raw as f64) as u16;
+        let percent = percent.max(self.minimum_brightness_percent);
+        let new_brightness = percent_to_raw(percent, self.max_brightness_raw);
 
-        if let Err(err) = fs::write(&current_brightness_path, new_brightness.to_string()) {
-            bail!("{}: {err}", current_brightness_path.display());
+        self.write_raw(new_brightness)?;
+        self.current_brightness_percent = percent;
+        Ok(())
+    }
+
+    fn step_brightness_fade(&mut self, percent: u8, step: u32) -> anyhow::Result<()> {
+        assert!(percent <= 100);
+
+        let percent = percent.max(self.minimum_brightness_percent);
+
+        // A fresh fade always starts at step 1: (re)compute where we're coming from and going to
+        // against our current state, so a fade that preempts another one continues smoothly from
+        // wherever that one left off instead of restarting from its original target.
+        if step == 1 {
+            self.fade_start_raw = self.current_brightness_raw;
+            self.fade_target_raw = percent_to_raw(percent, self.max_brightness_raw);
         }
 
-        self.current_brightness_raw = new_brightness;
-        self.current_brightness_percent = percent;
+        if self.fade_target_raw == self.fade_start_raw {
+            self.current_brightness_percent = percent;
+            return Ok(());
+        }
+
+        let eased = ease_in_out(step as f64 / FADE_STEPS as f64);
+        let raw = (self.fade_start_raw as f64
+            + (self.fade_target_raw as f64 - self.fade_start_raw as f64) * eased)
+            .round() as u16;
+
+        self.write_raw(raw)?;
+        // Reflect the value we actually just wrote, not the fade's eventual target, so a
+        // preempted/aborted fade reports where it really stopped instead of a stale percent.
+        self.current_brightness_percent = raw_to_percent(raw, self.max_brightness_raw);
+
         Ok(())
     }
 
+    fn resync_from_hardware(&mut self) -> bool {
+        let current_brightness_path = self.path.join("brightness");
+
+        let raw = match fs::read_to_string(&current_brightness_path) {
+            Ok(raw) => raw,
+            Err(err) => {
+                log::warn!("{}: {err}", current_brightness_path.display());
+                return false;
+            }
+        };
+
+        match raw.trim().parse::<u16>() {
+            // Matches what we last wrote ourselves: this event is an echo of our own write, not
+            // an external change.
+            Ok(raw) if raw == self.current_brightness_raw => false,
+            Ok(raw) => {
+                self.current_brightness_raw = raw;
+                self.current_brightness_percent = raw_to_percent(raw, self.max_brightness_raw);
+                true
+            }
+            Err(err) => {
+                log::warn!("{}: not a number: {err}", current_brightness_path.display());
+                false
+            }
+        }
+    }
+
     fn get_brightness(&self) -> u8 {
         self.current_brightness_percent
     }
 
+    fn minimum_brightness(&self) -> u8 {
+        self.minimum_brightness_percent
+    }
+
+    fn set_minimum_brightness(&mut self, percent: u8) {
+        assert!(percent <= 100);
+        self.minimum_brightness_percent = percent;
+    }
+
     fn name(&self) -> String {
         // It's ok to unwrap here, if there is no filename it means the developer did something wrong.
         self.path.file_name().unwrap().to_string_lossy().to_string()
@@ -74,3 +190,61 @@ impl BacklightDevice for BacklightAcpiDevice {
         todo!()
     }
 }
+
+/// Cosine ease-in-out: starts and ends slowly and moves faster through the middle, so a fade
+/// doesn't look as mechanical as a plain linear ramp.
+fn ease_in_out(t: f64) -> f64 {
+    0.5 - 0.5 * (std::f64::consts::PI * t).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_BRIGHTNESS_RAW: u16 = 1000;
+
+    #[test]
+    fn raw_to_percent_round_trips_at_the_bounds() {
+        assert_eq!(raw_to_percent(0, MAX_BRIGHTNESS_RAW), 0);
+        assert_eq!(raw_to_percent(MAX_BRIGHTNESS_RAW, MAX_BRIGHTNESS_RAW), 100);
+    }
+
+    #[test]
+    fn percent_to_raw_round_trips_at_the_bounds() {
+        assert_eq!(percent_to_raw(100, MAX_BRIGHTNESS_RAW), MAX_BRIGHTNESS_RAW);
+        assert_eq!(raw_to_percent(percent_to_raw(100, MAX_BRIGHTNESS_RAW), MAX_BRIGHTNESS_RAW), 100);
+    }
+
+    #[test]
+    fn percent_to_raw_never_goes_below_the_raw_floor() {
+        // 0% and 1% both sit under the gamma curve's natural floor, so they should clamp to the
+        // same minimum raw value instead of going fully black.
+        assert_eq!(percent_to_raw(0, MAX_BRIGHTNESS_RAW), minimum_raw(MAX_BRIGHTNESS_RAW));
+        assert_eq!(percent_to_raw(1, MAX_BRIGHTNESS_RAW), minimum_raw(MAX_BRIGHTNESS_RAW));
+        assert!(minimum_raw(MAX_BRIGHTNESS_RAW) > 0);
+    }
+
+    #[test]
+    fn percent_to_raw_is_monotonically_increasing() {
+        let mut previous = percent_to_raw(0, MAX_BRIGHTNESS_RAW);
+
+        for percent in 1..=100u8 {
+            let raw = percent_to_raw(percent, MAX_BRIGHTNESS_RAW);
+            assert!(raw >= previous, "percent_to_raw({percent}) = {raw} dipped below {previous}");
+            previous = raw;
+        }
+    }
+
+    #[test]
+    fn round_trip_stays_close_above_the_raw_floor() {
+        // Below the raw floor, percent_to_raw necessarily overstates the raw value (that's the
+        // floor's whole point), so round-tripping those percents back isn't meaningful; this
+        // only checks the range where the floor doesn't kick in.
+        for percent in 15..=100u8 {
+            let raw = percent_to_raw(percent, MAX_BRIGHTNESS_RAW);
+            let round_tripped = raw_to_percent(raw, MAX_BRIGHTNESS_RAW);
+            let diff = (round_tripped as i16 - percent as i16).abs();
+            assert!(diff <= 1, "percent {percent} round-tripped to {round_tripped} (raw {raw})");
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::{collections::VecDeque, fs, path::Path};
+
+use anyhow::{bail, Context};
+
+use crate::curve::{Curve, Key};
+
+const IIO_DEVICES_PATH: &str = "/sys/bus/iio/devices";
+
+/// Smooths noisy lux readings with a short moving average, so a single spurious sample (a hand
+/// passing over the sensor, a cloud) doesn't immediately yank the target brightness around.
+pub(crate) struct LuxSmoother {
+    samples: VecDeque<f64>,
+    capacity: usize,
+}
+
+impl LuxSmoother {
+    pub(crate) fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "a moving average needs at least one sample");
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Records a new reading and returns the average of the last `capacity` readings.
+    pub(crate) fn push(&mut self, lux: f64) -> f64 {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(lux);
+
+        self.samples.iter().sum::<f64>() / self.samples.len() as f64
+    }
+}
+
+/// Maps ambient light (in lux) to a target brightness percent, on top of the same monotone
+/// spline machinery `compute_brightness_percentage` uses for the time-of-day curve. Lux values
+/// below the first control point or above the last one are clamped.
+pub(crate) struct ResponseCurve {
+    curve: Curve,
+}
+
+impl ResponseCurve {
+    pub(crate) fn new(points: Vec<(f64, f64)>) -> Self {
+        let keys = points
+            .into_iter()
+            .map(|(lux, brightness)| Key { t: lux, brightness })
+            .collect();
+
+        Self {
+            curve: Curve::new(keys),
+        }
+    }
+
+    /// The default curve used until the user configures their own: a gentle ramp from a barely
+    /// lit room to full daylight.
+    pub(crate) fn default_curve() -> Self {
+        Self::new(vec![
+            (0., 5.),
+            (10., 15.),
+            (100., 40.),
+            (1_000., 70.),
+            (10_000., 90.),
+            (30_000., 100.),
+        ])
+    }
+
+    pub(crate) fn evaluate(&self, lux: f64) -> u8 {
+        self.curve.evaluate(lux).clamp(1., 100.).round() as u8
+    }
+}
+
+/// Reads the first ambient light sensor found under the IIO subsystem and returns its value in
+/// lux, already corrected by the device's `_scale` factor.
+pub(crate) fn read_lux() -> anyhow::Result<f64> {
+    let dir = fs::read_dir(IIO_DEVICES_PATH)
+        .with_context(|| format!("{IIO_DEVICES_PATH}: unable to list IIO devices"))?;
+
+    for entry in dir {
+        let path = entry?.path();
+
+        if let Some(lux) = try_read_illuminance(&path)? {
+            return Ok(lux);
+        }
+    }
+
+    bail!("No ambient light sensor found under {IIO_DEVICES_PATH}");
+}
+
+fn try_read_illuminance(device_path: &Path) -> anyhow::Result<Option<f64>> {
+    let raw_path = device_path.join("in_illuminance_raw");
+    let input_path = device_path.join("in_illuminance_input");
+    let scale_path = device_path.join("in_illuminance_scale");
+
+    let raw = match fs::read_to_string(&raw_path).or_else(|_| fs::read_to_string(&input_path)) {
+        Ok(raw) => raw,
+        Err(_) => return Ok(None),
+    };
+
+    let raw: f64 = raw
+        .trim()
+        .parse()
+        .with_context(|| format!("{}: not a number", device_path.display()))?;
+
+    let scale: f64 = match fs::read_to_string(&scale_path) {
+        Ok(scale) => scale.trim().parse().unwrap_or(1.),
+        Err(_) => 1.,
+    };
+
+    Ok(Some(raw * scale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_up_to_capacity_then_slides() {
+        let mut smoother = LuxSmoother::new(3);
+
+        assert_eq!(smoother.push(10.), 10.);
+        assert_eq!(smoother.push(20.), 15.);
+        assert_eq!(smoother.push(30.), 20.);
+        // A fourth sample pushes the first one (10.) out of the window.
+        assert_eq!(smoother.push(60.), (20. + 30. + 60.) / 3.);
+    }
+
+    #[test]
+    fn single_sample_capacity_tracks_the_latest_reading() {
+        let mut smoother = LuxSmoother::new(1);
+
+        assert_eq!(smoother.push(42.), 42.);
+        assert_eq!(smoother.push(7.), 7.);
+    }
+}
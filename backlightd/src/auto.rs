@@ -1,15 +1,30 @@
 use core::panic;
 use std::{
-    sync::mpsc::{Receiver, RecvTimeoutError},
+    sync::{
+        mpsc::{Receiver, RecvTimeoutError},
+        Mutex,
+    },
     time::{Duration, Instant},
 };
 
 use anyhow::anyhow;
 use backlight_ipc::BacklightMode;
-use chrono::{DateTime, Datelike, Local, NaiveTime};
+use chrono::{DateTime, Datelike, Local, NaiveTime, Timelike};
 use sunrise::sunrise_sunset;
 
-use crate::{location::find_location, monitors};
+use crate::curve::{Curve, Key};
+use crate::{ambient, location::find_location, monitors};
+
+/// The mode `auto_adjust` is currently operating in, mirrored here so other threads (the MQTT
+/// bridge) can report it without needing their own channel into this loop.
+static CURRENT_MODE: Mutex<BacklightMode> = Mutex::new(BacklightMode::Auto);
+
+pub(crate) fn current_mode() -> BacklightMode {
+    CURRENT_MODE
+        .lock()
+        .expect("Unable to acquire CURRENT_MODE mutex")
+        .clone()
+}
 
 const AUTO_ADJUST_INTERVAL: Duration = Duration::from_secs(600);
 
@@ -17,31 +32,56 @@ const BRIGHTNESS_TRANSITION_DURATION: Duration = Duration::from_secs(4 * 60 * 60
 const FALLBACK_BRIGHTNESS_UP_BEGIN: Option<NaiveTime> = NaiveTime::from_hms_opt(6, 0, 0);
 const FALLBACK_BRIGHTNESS_DOWN_BEGIN: Option<NaiveTime> = NaiveTime::from_hms_opt(18, 0, 0);
 
+// Cadence of the ambient-light poll loop: slow while the target brightness has settled, fast
+// for a few cycles right after a big change so the screen catches up quickly.
+const ADAPTIVE_SLOW_INTERVAL: Duration = Duration::from_secs(2);
+const ADAPTIVE_FAST_INTERVAL: Duration = Duration::from_millis(100);
+const ADAPTIVE_CHANGE_THRESHOLD: u8 = 3;
+
+// How many recent lux readings to average before mapping through the response curve.
+const ADAPTIVE_LUX_SMOOTHING_SAMPLES: usize = 5;
+
 pub fn auto_adjust(auto_adjust_receiver: Receiver<BacklightMode>) -> ! {
     let mut current_mode = BacklightMode::Auto;
     let mut last_time_mode_was_changed = Instant::now();
+    let mut last_adaptive_target: Option<u8> = None;
+    let ambient_curve = ambient::ResponseCurve::default_curve();
+    let mut lux_smoother = ambient::LuxSmoother::new(ADAPTIVE_LUX_SMOOTHING_SAMPLES);
 
     loop {
-        if matches!(current_mode, BacklightMode::Auto) {
-            let result = match find_location() {
-                Ok(Some((latitude, longitude))) => monitors::set_brightness_percent(
-                    get_brightness_based_on_location(latitude, longitude),
-                ),
-                Ok(None) => monitors::set_brightness_percent(get_brightness_based_on_time()),
-                Err(err) => Err(anyhow!("find location function failed: {err}")),
-            };
+        let next_interval = match current_mode {
+            BacklightMode::Auto => {
+                let result = match find_location() {
+                    Ok(Some((latitude, longitude))) => monitors::set_brightness_percent(
+                        get_brightness_based_on_location(latitude, longitude),
+                    ),
+                    Ok(None) => monitors::set_brightness_percent(get_brightness_based_on_time()),
+                    Err(err) => Err(anyhow!("find location function failed: {err}")),
+                };
 
-            if let Err(err) = result {
-                eprintln!("Unable to set brightness: {err}");
+                if let Err(err) = result {
+                    eprintln!("Unable to set brightness: {err}");
+                }
+
+                AUTO_ADJUST_INTERVAL
             }
-        }
+            BacklightMode::Adaptive => adjust_brightness_from_ambient_light(
+                &ambient_curve,
+                &mut lux_smoother,
+                &mut last_adaptive_target,
+            ),
+            BacklightMode::Manual => AUTO_ADJUST_INTERVAL,
+        };
 
-        match auto_adjust_receiver.recv_timeout(AUTO_ADJUST_INTERVAL) {
+        match auto_adjust_receiver.recv_timeout(next_interval) {
             Ok(new_mode) => {
                 if new_mode != current_mode {
                     println!("Set backlightd mode to {new_mode:?}");
                 }
                 last_time_mode_was_changed = Instant::now();
+                if new_mode != BacklightMode::Adaptive {
+                    last_adaptive_target = None;
+                }
                 current_mode = new_mode;
             }
             Err(err) => match err {
@@ -55,6 +95,44 @@ pub fn auto_adjust(auto_adjust_receiver: Receiver<BacklightMode>) -> ! {
         if Instant::now() - last_time_mode_was_changed > Duration::from_secs(12 * 60 * 60) {
             current_mode = BacklightMode::Auto;
         }
+
+        *CURRENT_MODE
+            .lock()
+            .expect("Unable to acquire CURRENT_MODE mutex") = current_mode.clone();
+    }
+}
+
+/// Reads the ambient light sensor, smooths it through `lux_smoother`, maps it to a target
+/// brightness through `curve`, and applies it if it has drifted from the last applied target by
+/// more than `ADAPTIVE_CHANGE_THRESHOLD`. Returns how long the caller should wait before polling
+/// again.
+fn adjust_brightness_from_ambient_light(
+    curve: &ambient::ResponseCurve,
+    lux_smoother: &mut ambient::LuxSmoother,
+    last_adaptive_target: &mut Option<u8>,
+) -> Duration {
+    let lux = match ambient::read_lux() {
+        Ok(lux) => lux,
+        Err(err) => {
+            eprintln!("Unable to read ambient light sensor: {err}");
+            return ADAPTIVE_SLOW_INTERVAL;
+        }
+    };
+
+    let target = curve.evaluate(lux_smoother.push(lux));
+    let settled = last_adaptive_target.is_some_and(|last| target.abs_diff(last) <= ADAPTIVE_CHANGE_THRESHOLD);
+
+    if !settled {
+        if let Err(err) = monitors::set_brightness_percent(target) {
+            eprintln!("Unable to set brightness: {err}");
+        }
+        *last_adaptive_target = Some(target);
+    }
+
+    if settled {
+        ADAPTIVE_SLOW_INTERVAL
+    } else {
+        ADAPTIVE_FAST_INTERVAL
     }
 }
 
@@ -88,6 +166,10 @@ fn get_brightness_based_on_time() -> u8 {
     )
 }
 
+/// Builds the default four-point day curve (dim at night, ramping up around `brightness_up_*`,
+/// full brightness during the day, ramping down around `brightness_down_*`) and evaluates it at
+/// `now`. Times before `brightness_up_begin` or after `brightness_down_end` clamp to the curve's
+/// first/last key (1%), which is exactly the old "night" behaviour.
 fn compute_brightness_percentage(
     now: NaiveTime,
     brightness_up_begin: NaiveTime,
@@ -99,20 +181,23 @@ fn compute_brightness_percentage(
     assert!(brightness_up_end < brightness_down_begin);
     assert!(brightness_down_begin < brightness_down_end);
 
-    if now < brightness_up_begin || now > brightness_down_end {
-        1
-    } else if now > brightness_up_end && now < brightness_down_begin {
-        100
-    } else if now >= brightness_up_begin && now <= brightness_up_end {
-        let duration = (brightness_up_end - brightness_up_begin).num_seconds() as f64;
-        let elapsed = (now - brightness_up_begin).num_seconds() as f64;
-        ((elapsed / duration * 99.) + 1.).round() as u8
-    } else if now >= brightness_down_begin && now <= brightness_down_end {
-        let duration = (brightness_down_end - brightness_down_begin).num_seconds() as f64;
-        let elapsed = (now - brightness_down_begin).num_seconds() as f64;
-        ((1. - elapsed / duration) * 99. + 1.).round() as u8
-    } else {
-        unreachable!()
+    let curve = Curve::new(vec![
+        key_at(brightness_up_begin, 1.),
+        key_at(brightness_up_end, 100.),
+        key_at(brightness_down_begin, 100.),
+        key_at(brightness_down_end, 1.),
+    ]);
+
+    curve
+        .evaluate(now.num_seconds_from_midnight() as f64)
+        .round()
+        .clamp(1., 100.) as u8
+}
+
+fn key_at(t: NaiveTime, brightness: f64) -> Key {
+    Key {
+        t: t.num_seconds_from_midnight() as f64,
+        brightness,
     }
 }
 
@@ -0,0 +1,182 @@
+/// A control point mapping a scalar `t` (e.g. seconds since midnight, or lux) to a brightness
+/// percent.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Key {
+    pub(crate) t: f64,
+    pub(crate) brightness: f64,
+}
+
+/// A monotone cubic (Fritsch-Carlson) spline through an ordered set of `Key` points. Queries
+/// outside the first/last key are clamped to that key's brightness. Unlike a plain Catmull-Rom
+/// spline, tangents are limited so the curve never overshoots past two neighbouring control
+/// points -- important since brightness must stay within [1, 100].
+pub(crate) struct Curve {
+    keys: Vec<Key>,
+    tangents: Vec<f64>,
+}
+
+impl Curve {
+    pub(crate) fn new(mut keys: Vec<Key>) -> Self {
+        assert!(keys.len() >= 2, "a curve needs at least two keys");
+        keys.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
+
+        let tangents = monotone_tangents(&keys);
+
+        Self { keys, tangents }
+    }
+
+    pub(crate) fn evaluate(&self, t: f64) -> f64 {
+        let keys = &self.keys;
+
+        if t <= keys[0].t {
+            return keys[0].brightness;
+        }
+
+        if t >= keys[keys.len() - 1].t {
+            return keys[keys.len() - 1].brightness;
+        }
+
+        let segment = keys
+            .windows(2)
+            .position(|w| t >= w[0].t && t <= w[1].t)
+            .expect("t should fall within the curve's bounds");
+
+        let (k0, k1) = (keys[segment], keys[segment + 1]);
+        let (m0, m1) = (self.tangents[segment], self.tangents[segment + 1]);
+        let h = k1.t - k0.t;
+        let u = (t - k0.t) / h;
+
+        let u2 = u * u;
+        let u3 = u2 * u;
+
+        let h00 = 2. * u3 - 3. * u2 + 1.;
+        let h10 = u3 - 2. * u2 + u;
+        let h01 = -2. * u3 + 3. * u2;
+        let h11 = u3 - u2;
+
+        let value = h00 * k0.brightness + h10 * h * m0 + h01 * k1.brightness + h11 * h * m1;
+
+        // The monotone cubic stays between its two keys; clamp away floating point rounding that
+        // would otherwise land a hair outside them.
+        value.clamp(k0.brightness.min(k1.brightness), k0.brightness.max(k1.brightness))
+    }
+}
+
+/// Fritsch-Carlson tangents: start from secant-averaged estimates, then rescale any pair whose
+/// combined slope ratio would make the cubic overshoot the secant between two keys.
+fn monotone_tangents(keys: &[Key]) -> Vec<f64> {
+    let n = keys.len();
+
+    let secants: Vec<f64> = (0..n - 1)
+        .map(|i| (keys[i + 1].brightness - keys[i].brightness) / (keys[i + 1].t - keys[i].t))
+        .collect();
+
+    let mut tangents = vec![0.; n];
+    tangents[0] = secants[0];
+    tangents[n - 1] = secants[n - 2];
+
+    for i in 1..n - 1 {
+        let (prev, next) = (secants[i - 1], secants[i]);
+
+        tangents[i] = if prev == 0. || next == 0. || prev.signum() != next.signum() {
+            0.
+        } else {
+            (prev + next) / 2.
+        };
+    }
+
+    for i in 0..n - 1 {
+        let d = secants[i];
+
+        if d == 0. {
+            tangents[i] = 0.;
+            tangents[i + 1] = 0.;
+            continue;
+        }
+
+        let alpha = tangents[i] / d;
+        let beta = tangents[i + 1] / d;
+        let dist = alpha.powi(2) + beta.powi(2);
+
+        if dist > 9. {
+            let scale = 3. / dist.sqrt();
+            tangents[i] = scale * alpha * d;
+            tangents[i + 1] = scale * beta * d;
+        }
+    }
+
+    tangents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(t: f64, brightness: f64) -> Key {
+        Key { t, brightness }
+    }
+
+    #[test]
+    fn clamps_outside_the_key_range() {
+        let curve = Curve::new(vec![key(0., 1.), key(10., 100.)]);
+
+        assert_eq!(curve.evaluate(-5.), 1.);
+        assert_eq!(curve.evaluate(15.), 100.);
+    }
+
+    #[test]
+    fn reproduces_exact_values_at_keys() {
+        let curve = Curve::new(vec![key(0., 1.), key(5., 40.), key(10., 100.), key(20., 100.), key(30., 1.)]);
+
+        for k in [0., 5., 10., 20., 30.] {
+            assert_eq!(curve.evaluate(k), curve.evaluate(k).round());
+            assert!((curve.evaluate(k) - match k {
+                0. => 1.,
+                5. => 40.,
+                10. => 100.,
+                20. => 100.,
+                30. => 1.,
+                _ => unreachable!(),
+            }).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn stays_flat_across_a_plateau() {
+        // Two adjacent keys at the same brightness should never dip or overshoot in between,
+        // which is exactly the "full brightness at midday" segment `auto.rs` relies on.
+        let curve = Curve::new(vec![key(0., 1.), key(10., 100.), key(20., 100.), key(30., 1.)]);
+
+        let mut t = 10.;
+        while t <= 20. {
+            assert_eq!(curve.evaluate(t), 100.);
+            t += 0.5;
+        }
+    }
+
+    #[test]
+    fn never_overshoots_past_its_keys() {
+        let curve = Curve::new(vec![key(0., 1.), key(10., 100.), key(20., 100.), key(30., 1.)]);
+
+        let mut t = 0.;
+        while t <= 30. {
+            let value = curve.evaluate(t);
+            assert!((1. ..=100.).contains(&value), "evaluate({t}) = {value} escaped [1, 100]");
+            t += 0.25;
+        }
+    }
+
+    #[test]
+    fn is_monotonic_along_a_monotonic_segment() {
+        let curve = Curve::new(vec![key(0., 1.), key(10., 100.)]);
+
+        let mut previous = curve.evaluate(0.);
+        let mut t = 0.5;
+        while t <= 10. {
+            let value = curve.evaluate(t);
+            assert!(value >= previous, "evaluate({t}) = {value} dipped below {previous}");
+            previous = value;
+            t += 0.5;
+        }
+    }
+}
@@ -1,41 +1,113 @@
 /// The bible for DDC:
 /// https://milek7.pl/ddcbacklight/mccs.pdf
-use std::error::Error;
+use std::{
+    error::Error,
+    hash::{Hash, Hasher},
+};
 
 use anyhow::bail;
 use ddc_hi::{Ddc, Display, FeatureCode};
 
+use crate::ddc_cache::{self, CachedDdcMonitor};
 use crate::monitors::BacklightDevice;
 
 const VCP_FEATURE_BRIGHTNESS: FeatureCode = 0x10;
+const VCP_FEATURE_CONTRAST: FeatureCode = 0x12;
 const VCP_FEATURE_POWER: FeatureCode = 0xD6;
 const VCP_VALUE_POWER_ON: u16 = 0x1;
 const VCP_VALUE_POWER_OFF: u16 = 0x4;
 
+/// Mirrors `acpi::DEFAULT_MINIMUM_BRIGHTNESS_PERCENT`: never drive a monitor fully black unless
+/// the user asks for it explicitly.
+const DEFAULT_MINIMUM_BRIGHTNESS_PERCENT: u8 = 1;
+
 pub(crate) struct BacklightDdcDevice {
     display: Display,
+    cache_key: String,
     max_brightness_raw: u16,
     current_brightness_raw: u16,
     current_brightness_percent: u8,
+    minimum_brightness_percent: u8,
+    supports_power: bool,
+    /// The monitor's maximum contrast VCP value, when it supports feature 0x12. We don't let
+    /// users set contrast independently yet, just keep it at the same percent as brightness.
+    max_contrast_raw: Option<u16>,
 }
 
 impl BacklightDdcDevice {
     pub(crate) fn new(mut ddc_device: ddc_hi::Display) -> Result<Self, Box<dyn Error>> {
+        let cache_key = stable_cache_key(&ddc_device.info);
+
+        if let Some(cached) = ddc_cache::get(&cache_key) {
+            return Ok(Self {
+                display: ddc_device,
+                cache_key,
+                max_brightness_raw: cached.max_brightness_raw,
+                current_brightness_raw: cached.last_brightness_raw,
+                current_brightness_percent: (cached.last_brightness_raw * 100
+                    / cached.max_brightness_raw) as u8,
+                minimum_brightness_percent: DEFAULT_MINIMUM_BRIGHTNESS_PERCENT,
+                supports_power: cached.supports_power,
+                max_contrast_raw: cached.max_contrast_raw,
+            });
+        }
+
         let brightness = ddc_device.handle.get_vcp_feature(VCP_FEATURE_BRIGHTNESS)?;
+        let supports_power = ddc_device
+            .handle
+            .get_vcp_feature(VCP_FEATURE_POWER)
+            .is_ok();
+        let max_contrast_raw = ddc_device
+            .handle
+            .get_vcp_feature(VCP_FEATURE_CONTRAST)
+            .ok()
+            .map(|contrast| contrast.maximum());
+
+        ddc_cache::put(
+            &cache_key,
+            &CachedDdcMonitor {
+                max_brightness_raw: brightness.maximum(),
+                last_brightness_raw: brightness.value(),
+                supports_power,
+                max_contrast_raw,
+            },
+        );
 
         Ok(Self {
             display: ddc_device,
+            cache_key,
             max_brightness_raw: brightness.maximum(),
             current_brightness_raw: brightness.value(),
             current_brightness_percent: (brightness.value() * 100 / brightness.maximum()) as u8,
+            minimum_brightness_percent: DEFAULT_MINIMUM_BRIGHTNESS_PERCENT,
+            supports_power,
+            max_contrast_raw,
         })
     }
 }
 
+/// A key that should stay stable across reboots and re-enumerations for a given physical
+/// monitor, so cached VCP capabilities can be reattached to it: prefer the EDID serial number,
+/// falling back to a hash of the raw EDID when the monitor doesn't report one.
+fn stable_cache_key(info: &ddc_hi::DisplayInfo) -> String {
+    if let Some(serial) = info.serial_number.as_ref().filter(|s| !s.is_empty()) {
+        return serial.clone();
+    }
+
+    if !info.edid_data.is_empty() {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        info.edid_data.hash(&mut hasher);
+        return format!("edid-{:x}", hasher.finish());
+    }
+
+    info.id.clone()
+}
+
 impl BacklightDevice for BacklightDdcDevice {
     fn set_brightness(&mut self, percent: u8) -> anyhow::Result<()> {
         assert!(percent <= 100);
 
+        let percent = percent.max(self.minimum_brightness_percent);
         let new_brightness = (percent as f64 / 100. * self.max_brightness_raw as f64) as u16;
 
         if let Err(err) = self
@@ -43,11 +115,37 @@ impl BacklightDevice for BacklightDdcDevice {
             .handle
             .set_vcp_feature(VCP_FEATURE_BRIGHTNESS, new_brightness)
         {
+            // Our cached capabilities/address might be stale, force a re-probe next refresh.
+            ddc_cache::invalidate(&self.cache_key);
             bail!("{}: {err}", self.name());
         }
 
         self.current_brightness_raw = new_brightness;
         self.current_brightness_percent = percent;
+
+        if let Some(max_contrast_raw) = self.max_contrast_raw {
+            let new_contrast = (percent as f64 / 100. * max_contrast_raw as f64) as u16;
+
+            if let Err(err) = self
+                .display
+                .handle
+                .set_vcp_feature(VCP_FEATURE_CONTRAST, new_contrast)
+            {
+                // Contrast is a nice-to-have alongside brightness; don't fail the whole call or
+                // invalidate the cache over it.
+                log::warn!("{}: unable to set contrast: {err}", self.name());
+            }
+        }
+
+        ddc_cache::put(
+            &self.cache_key,
+            &CachedDdcMonitor {
+                max_brightness_raw: self.max_brightness_raw,
+                last_brightness_raw: new_brightness,
+                supports_power: self.supports_power,
+                max_contrast_raw: self.max_contrast_raw,
+            },
+        );
         Ok(())
     }
 
@@ -55,6 +153,15 @@ impl BacklightDevice for BacklightDdcDevice {
         self.current_brightness_percent
     }
 
+    fn minimum_brightness(&self) -> u8 {
+        self.minimum_brightness_percent
+    }
+
+    fn set_minimum_brightness(&mut self, percent: u8) {
+        assert!(percent <= 100);
+        self.minimum_brightness_percent = percent;
+    }
+
     fn name(&self) -> String {
         self.display
             .info
@@ -64,11 +171,16 @@ impl BacklightDevice for BacklightDdcDevice {
     }
 
     fn turn_off(&mut self) -> anyhow::Result<()> {
+        if !self.supports_power {
+            bail!("{}: monitor does not support the power VCP feature", self.name());
+        }
+
         if let Err(err) = self
             .display
             .handle
             .set_vcp_feature(VCP_FEATURE_POWER, VCP_VALUE_POWER_OFF)
         {
+            ddc_cache::invalidate(&self.cache_key);
             bail!("{}: {err}", self.name());
         }
 
@@ -76,11 +188,16 @@ impl BacklightDevice for BacklightDdcDevice {
     }
 
     fn turn_on(&mut self) -> anyhow::Result<()> {
+        if !self.supports_power {
+            bail!("{}: monitor does not support the power VCP feature", self.name());
+        }
+
         if let Err(err) = self
             .display
             .handle
             .set_vcp_feature(VCP_FEATURE_POWER, VCP_VALUE_POWER_ON)
         {
+            ddc_cache::invalidate(&self.cache_key);
             bail!("{}: {err}", self.name());
         }
 
@@ -0,0 +1,108 @@
+use std::{fs, path::Path, sync::OnceLock};
+
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+
+const CACHE_PATH: &str = "/var/cache/backlightd/ddc_cache.redb";
+const TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("ddc_monitors");
+
+/// What we remember about a DDC monitor between two runs of `refresh_monitors_list`, so we don't
+/// have to re-probe its VCP capabilities over I2C (slow, and occasionally flaky) every time.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct CachedDdcMonitor {
+    pub(crate) max_brightness_raw: u16,
+    pub(crate) last_brightness_raw: u16,
+    pub(crate) supports_power: bool,
+    /// The monitor's maximum contrast VCP value (feature 0x12), or `None` if it doesn't expose
+    /// that feature. When present, contrast is kept in lockstep with brightness.
+    pub(crate) max_contrast_raw: Option<u16>,
+}
+
+/// The redb `Database` handle, opened once and shared across threads. redb refuses to open a
+/// file it already has open in the same process, so re-opening it on every cache operation would
+/// make concurrent callers (the socket handler and the MQTT bridge both call into this module)
+/// race for the file and silently drop whichever write loses.
+static DATABASE: OnceLock<anyhow::Result<Database>> = OnceLock::new();
+
+fn open_database() -> anyhow::Result<&'static Database> {
+    DATABASE
+        .get_or_init(|| {
+            if let Some(parent) = Path::new(CACHE_PATH).parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            Ok(Database::create(CACHE_PATH)?)
+        })
+        .as_ref()
+        .map_err(|err| anyhow::anyhow!("{err}"))
+}
+
+/// Looks up a cached entry by the monitor's stable key. Any failure to open or read the cache is
+/// treated as a cache miss: the caller just falls back to re-probing the monitor.
+pub(crate) fn get(key: &str) -> Option<CachedDdcMonitor> {
+    match try_get(key) {
+        Ok(entry) => entry,
+        Err(err) => {
+            log::warn!("Unable to read DDC cache entry for {key}: {err}");
+            None
+        }
+    }
+}
+
+fn try_get(key: &str) -> anyhow::Result<Option<CachedDdcMonitor>> {
+    let db = open_database()?;
+    let read_txn = db.begin_read()?;
+
+    let table = match read_txn.open_table(TABLE) {
+        Ok(table) => table,
+        Err(redb::TableError::TableDoesNotExist(_)) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    match table.get(key)? {
+        Some(value) => Ok(Some(bincode::deserialize(value.value())?)),
+        None => Ok(None),
+    }
+}
+
+/// Stores (or overwrites) the cached entry for `key`.
+pub(crate) fn put(key: &str, entry: &CachedDdcMonitor) {
+    if let Err(err) = try_put(key, entry) {
+        log::warn!("Unable to write DDC cache entry for {key}: {err}");
+    }
+}
+
+fn try_put(key: &str, entry: &CachedDdcMonitor) -> anyhow::Result<()> {
+    let db = open_database()?;
+    let write_txn = db.begin_write()?;
+
+    {
+        let mut table = write_txn.open_table(TABLE)?;
+        table.insert(key, bincode::serialize(entry)?.as_slice())?;
+    }
+
+    write_txn.commit()?;
+    Ok(())
+}
+
+/// Drops the cached entry for `key` so the next refresh re-probes the monitor from scratch.
+/// Called whenever a VCP write fails, since that usually means our cached state (or the
+/// monitor's I2C address) is no longer accurate.
+pub(crate) fn invalidate(key: &str) {
+    if let Err(err) = try_invalidate(key) {
+        log::warn!("Unable to invalidate DDC cache entry for {key}: {err}");
+    }
+}
+
+fn try_invalidate(key: &str) -> anyhow::Result<()> {
+    let db = open_database()?;
+    let write_txn = db.begin_write()?;
+
+    {
+        let mut table = write_txn.open_table(TABLE)?;
+        table.remove(key)?;
+    }
+
+    write_txn.commit()?;
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::{fs, sync::mpsc::Sender, thread, time::Duration};
+
+use anyhow::bail;
+use backlight_ipc::BacklightMode;
+use inotify::{Inotify, WatchMask};
+
+use crate::{acpi::ACPI_DEVICES_PATH, monitors};
+
+/// Watches every ACPI backlight device's `brightness` file for writes, since that's the only
+/// signal a hardware hotkey gives us -- it writes straight to sysfs, bypassing us entirely. Most
+/// such events are actually echoes of our own writes (every `set_brightness`/fade step touches
+/// the same file), so we always resync and let `resync_from_hardware` tell us whether the value
+/// genuinely changed before flipping to `Manual` mode the same way an explicit `SetBrightness`
+/// would; otherwise auto-adjust and adaptive mode would never survive their own first write.
+pub(crate) fn watch_hardware_brightness_changes(auto_adjust_sender: Sender<BacklightMode>) -> ! {
+    loop {
+        if let Err(err) = watch_once(&auto_adjust_sender) {
+            log::error!("Hardware brightness watch failed, retrying in 10s: {err}");
+        }
+
+        thread::sleep(Duration::from_secs(10));
+    }
+}
+
+fn watch_once(auto_adjust_sender: &Sender<BacklightMode>) -> anyhow::Result<()> {
+    let mut inotify = Inotify::init()?;
+    let mut watch_count = 0;
+
+    for entry in fs::read_dir(ACPI_DEVICES_PATH)? {
+        let device_path = entry?.path();
+
+        for file_name in ["brightness", "actual_brightness"] {
+            let watched_path = device_path.join(file_name);
+
+            if watched_path.exists() {
+                inotify
+                    .watches()
+                    .add(&watched_path, WatchMask::MODIFY | WatchMask::CLOSE_WRITE)?;
+                watch_count += 1;
+            }
+        }
+    }
+
+    if watch_count == 0 {
+        bail!("No ACPI backlight brightness files found under {ACPI_DEVICES_PATH}");
+    }
+
+    let mut buffer = [0; 4096];
+
+    loop {
+        let events = inotify.read_events_blocking(&mut buffer)?;
+
+        if events.count() == 0 {
+            continue;
+        }
+
+        if monitors::resync_from_hardware() {
+            auto_adjust_sender
+                .send(BacklightMode::Manual)
+                .unwrap_or_else(|err| log::error!("Failed to send mode to auto adjust channel: {err}"));
+        }
+    }
+}
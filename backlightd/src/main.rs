@@ -1,7 +1,7 @@
 use std::{
     env,
     fs::{self, remove_file},
-    io,
+    io::{self, Read},
     os::unix::{
         fs::PermissionsExt,
         net::{UnixListener, UnixStream},
@@ -15,13 +15,20 @@ use std::{
 use anyhow::{anyhow, bail};
 use auto::auto_adjust;
 use backlight_ipc::{BacklightCommand, BacklightInfo, BacklightMode, DEFAULT_UNIX_SOCKET_PATH};
+use hotkeys::watch_hardware_brightness_changes;
 use monitors::auto_refresh_monitors_list;
+use mqtt::mqtt_bridge_thread;
 
 mod acpi;
+mod ambient;
 mod auto;
+mod curve;
 mod ddc;
+mod ddc_cache;
+mod hotkeys;
 mod location;
 mod monitors;
+mod mqtt;
 
 fn main() {
     pretty_env_logger::formatted_builder()
@@ -58,7 +65,15 @@ fn main() {
 
     let auto_refresh_monitors_thread = thread::spawn(move || auto_refresh_monitors_list());
     let auto_adjust_thread = thread::spawn(move || auto_adjust(auto_receiver));
-    let handle_clients_thread = thread::spawn(move || handle_clients_thread(listener, auto_sender));
+    let handle_clients_thread = {
+        let auto_sender = auto_sender.clone();
+        thread::spawn(move || handle_clients_thread(listener, auto_sender))
+    };
+    let mqtt_bridge_thread = {
+        let auto_sender = auto_sender.clone();
+        thread::spawn(move || mqtt_bridge_thread(auto_sender))
+    };
+    let hotkeys_thread = thread::spawn(move || watch_hardware_brightness_changes(auto_sender));
 
     loop {
         if auto_refresh_monitors_thread.is_finished() {
@@ -73,6 +88,14 @@ fn main() {
             panic!("handle_clients_thread thread is gone");
         }
 
+        if mqtt_bridge_thread.is_finished() {
+            panic!("mqtt bridge thread is gone");
+        }
+
+        if hotkeys_thread.is_finished() {
+            panic!("hotkeys thread is gone");
+        }
+
         sleep(Duration::from_secs(5));
     }
 }
@@ -118,49 +141,28 @@ fn handle_client(
         };
 
         let result = match command {
-            BacklightCommand::SetBrightness(percent) => {
-                auto_adjust_sender
-                    .send(BacklightMode::Manual)
-                    .expect("Failed to send BacklightMode through auto adjust channel");
-                monitors::set_brightness_percent(percent)
-            }
-            BacklightCommand::IncreaseBrightness(percent) => {
-                auto_adjust_sender
-                    .send(BacklightMode::Manual)
-                    .expect("Failed to send BacklightMode through auto adjust channel");
-                monitors::increase_brightness_percent(percent)
-            }
-            BacklightCommand::DecreaseBrightness(percent) => {
-                auto_adjust_sender
-                    .send(BacklightMode::Manual)
-                    .expect("Failed to send BacklightMode through auto adjust channel");
-                monitors::decrease_brightness_percent(percent)
-            }
-            BacklightCommand::Refresh => {
-                monitors::refresh_monitors_list();
-                Ok(())
-            }
-            BacklightCommand::SetMode(backlight_mode) => {
-                auto_adjust_sender
-                    .send(backlight_mode)
-                    .unwrap_or_else(|err| {
-                        log::error!("Failed to send mode to auto adjust channel: {err}")
-                    });
-                Ok(())
-            }
             BacklightCommand::GetInfo => {
-                BacklightCommand::GetInfoResponse(BacklightInfo {
-                    brightness_percent: monitors::get_average_brightness(),
-                })
-                .serialize_into(&client)
-                .unwrap_or_else(|err| log::error!("Unable to serialize GetInfoResponse: {err}"));
+                BacklightCommand::GetInfoResponse(current_info())
+                    .serialize_into(&client)
+                    .unwrap_or_else(|err| log::error!("Unable to serialize GetInfoResponse: {err}"));
                 Ok(())
             }
             BacklightCommand::GetInfoResponse(_) => {
                 log::warn!("Got GetInfoResponse from client, API misuse ?");
                 Ok(())
             }
+            BacklightCommand::Watch => {
+                // A watcher stays connected indefinitely, so serve it on its own thread instead of
+                // the one that handles the socket's other clients one at a time.
+                thread::spawn(move || {
+                    if let Err(err) = watch_client(&client) {
+                        log::info!("Watching client disconnected: {err}");
+                    }
+                });
+                break;
+            }
             BacklightCommand::NotifyShutdown => break,
+            command => execute_command(command, &auto_adjust_sender),
         };
 
         if let Err(err) = result {
@@ -170,3 +172,103 @@ fn handle_client(
 
     Ok(())
 }
+
+/// How often we check whether brightness/mode changed while a client is watching via
+/// `BacklightCommand::Watch`. Hanging-get: we only write to the client when the observed state
+/// actually differs from what it last saw, so idle watchers cost nothing but a cheap comparison.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Keeps pushing `GetInfoResponse` down `client` whenever brightness or mode changes, until the
+/// client disconnects (or some other read/write error occurs).
+fn watch_client(client: &UnixStream) -> anyhow::Result<()> {
+    // A watching client never sends anything back, so we reuse the wait between polls as a
+    // disconnect probe: a timed-out read means it's still there and idle, `Ok(0)` means it closed
+    // its end. Without this, an idle client that simply vanishes (no more brightness/mode changes
+    // to notice it on) would leak its thread here forever.
+    client
+        .set_read_timeout(Some(WATCH_POLL_INTERVAL))
+        .map_err(|err| anyhow!("Unable to set read timeout on watching client: {err}"))?;
+
+    let mut last_sent = current_info();
+
+    BacklightCommand::GetInfoResponse(last_sent.clone())
+        .serialize_into(client)
+        .map_err(|err| anyhow!("Unable to serialize GetInfoResponse: {err}"))?;
+
+    let mut probe = [0u8; 1];
+
+    loop {
+        match (&*client).read(&mut probe) {
+            Ok(0) => bail!("watching client disconnected"),
+            Ok(_) => bail!("watching client unexpectedly sent data"),
+            Err(err) if matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+            Err(err) => bail!("Unable to poll watching client: {err}"),
+        }
+
+        let info = current_info();
+
+        if info != last_sent {
+            BacklightCommand::GetInfoResponse(info.clone())
+                .serialize_into(client)
+                .map_err(|err| anyhow!("Unable to serialize GetInfoResponse: {err}"))?;
+            last_sent = info;
+        }
+    }
+}
+
+/// Runs a command's side effect against the shared monitors/auto-adjust state. Shared between
+/// the Unix-socket handler and the MQTT bridge; `GetInfo`/`GetInfoResponse`/`NotifyShutdown` are
+/// connection-specific and handled by their respective callers instead.
+pub(crate) fn execute_command(
+    command: BacklightCommand,
+    auto_adjust_sender: &Sender<BacklightMode>,
+) -> anyhow::Result<()> {
+    match command {
+        BacklightCommand::SetBrightness(percent, target) => {
+            auto_adjust_sender
+                .send(BacklightMode::Manual)
+                .expect("Failed to send BacklightMode through auto adjust channel");
+            monitors::fade_brightness_percent_for(percent, &target)
+        }
+        BacklightCommand::IncreaseBrightness(percent, target) => {
+            auto_adjust_sender
+                .send(BacklightMode::Manual)
+                .expect("Failed to send BacklightMode through auto adjust channel");
+            monitors::fade_increase_brightness_percent_for(percent, &target)
+        }
+        BacklightCommand::DecreaseBrightness(percent, target) => {
+            auto_adjust_sender
+                .send(BacklightMode::Manual)
+                .expect("Failed to send BacklightMode through auto adjust channel");
+            monitors::fade_decrease_brightness_percent_for(percent, &target)
+        }
+        BacklightCommand::TurnOffMonitors(target) => monitors::turn_off_for(&target),
+        BacklightCommand::TurnOnMonitors(target) => monitors::turn_on_for(&target),
+        BacklightCommand::SetMinimumBrightness(percent, target) => {
+            monitors::set_minimum_brightness_for(percent, &target);
+            Ok(())
+        }
+        BacklightCommand::Refresh => {
+            monitors::refresh_monitors_list();
+            Ok(())
+        }
+        BacklightCommand::SetMode(backlight_mode) => {
+            auto_adjust_sender
+                .send(backlight_mode)
+                .unwrap_or_else(|err| log::error!("Failed to send mode to auto adjust channel: {err}"));
+            Ok(())
+        }
+        BacklightCommand::GetInfo
+        | BacklightCommand::GetInfoResponse(_)
+        | BacklightCommand::Watch
+        | BacklightCommand::NotifyShutdown => Ok(()),
+    }
+}
+
+pub(crate) fn current_info() -> BacklightInfo {
+    BacklightInfo {
+        brightness_percent: monitors::get_average_brightness(),
+        monitors: monitors::get_monitors_info(),
+        mode: auto::current_mode(),
+    }
+}
@@ -1,10 +1,15 @@
 use std::{
     fs::{self},
-    sync::Mutex,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
     thread,
     time::{Duration, Instant},
 };
 
+use backlight_ipc::{BacklightTarget, MonitorInfo};
+
 use crate::acpi::{BacklightAcpiDevice, ACPI_DEVICES_PATH};
 use crate::ddc::BacklightDdcDevice;
 
@@ -20,12 +25,49 @@ static LAST_REFRESH: Mutex<Option<Instant>> = Mutex::new(None);
 /// The frequency at which the list of known monitors must be refreshed.
 const MONITORS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
 
+/// Bumped every time a faded brightness change is requested, so a fade already in progress on
+/// another thread can notice a newer request arrived and cut itself short instead of finishing
+/// first and making the new command queue behind it.
+static FADE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// How long a faded brightness change takes from start to finish.
+pub(crate) const FADE_DURATION: Duration = Duration::from_millis(300);
+/// How many intermediate steps we write over `FADE_DURATION`.
+pub(crate) const FADE_STEPS: u32 = 30;
+
 pub(crate) trait BacklightDevice {
     fn name(&self) -> String;
     fn set_brightness(&mut self, percent: u8) -> anyhow::Result<()>;
     fn get_brightness(&self) -> u8;
+    fn minimum_brightness(&self) -> u8;
+    fn set_minimum_brightness(&mut self, percent: u8);
     fn turn_off(&mut self) -> anyhow::Result<()>;
     fn turn_on(&mut self) -> anyhow::Result<()>;
+
+    /// Re-reads this device's current state directly from hardware, to pick up changes that
+    /// didn't go through `set_brightness` (e.g. a hardware hotkey writing sysfs directly).
+    /// Returns whether the on-disk value actually differed from what we last wrote ourselves, so
+    /// callers watching for external changes (like the hotkey watcher) don't mistake our own
+    /// writes for a hotkey press. No-op by default; only ACPI devices currently need this.
+    fn resync_from_hardware(&mut self) -> bool {
+        false
+    }
+
+    /// Advances one step (1-indexed, out of `FADE_STEPS`) of a fade toward `percent`. The caller
+    /// drives the timing and only holds the `MONITORS` lock for the duration of a single step
+    /// (not the whole fade), so other operations -- `GetInfo`, `Watch`, the MQTT status poll,
+    /// auto-adjust, a hardware resync -- aren't blocked behind an in-progress fade. A fresh fade
+    /// always starts at `step == 1`, which devices that track fade progress use to (re)compute
+    /// their start/target. Devices that can't meaningfully fade (e.g. DDC, which already pays
+    /// I2C latency per write) can just jump straight to `percent` on the first step and ignore
+    /// the rest.
+    fn step_brightness_fade(&mut self, percent: u8, step: u32) -> anyhow::Result<()> {
+        if step == 1 {
+            self.set_brightness(percent)
+        } else {
+            Ok(())
+        }
+    }
 }
 
 pub(crate) fn auto_refresh_monitors_list() -> ! {
@@ -87,10 +129,26 @@ pub(crate) fn refresh_monitors_list() {
         .expect("Unable to acquire LAST_REFRESH mutex") = Some(Instant::now());
 }
 
+fn matches_target(monitor: &dyn BacklightDevice, target: &BacklightTarget) -> bool {
+    match target {
+        BacklightTarget::All => true,
+        BacklightTarget::Monitor(name) => &monitor.name() == name,
+    }
+}
+
 pub(crate) fn set_brightness_percent(percent: u8) -> anyhow::Result<()> {
+    set_brightness_percent_for(percent, &BacklightTarget::All)
+}
+
+pub(crate) fn set_brightness_percent_for(percent: u8, target: &BacklightTarget) -> anyhow::Result<()> {
     let mut last_error = None;
 
-    for monitor in MONITORS.lock().unwrap().iter_mut() {
+    for monitor in MONITORS
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .filter(|monitor| matches_target(monitor.as_ref(), target))
+    {
         let res = monitor.set_brightness(percent);
 
         if let Err(err) = res {
@@ -104,15 +162,92 @@ pub(crate) fn set_brightness_percent(percent: u8) -> anyhow::Result<()> {
         refresh_monitors_list();
         Err(err)
     } else {
-        log::info!("Brightness of all monitors has been set to {percent}%");
+        log::info!("Brightness has been set to {percent}%");
+        Ok(())
+    }
+}
+
+/// Drives a multi-step fade for every monitor matching `target`, without holding the `MONITORS`
+/// lock across the inter-step sleeps: each step takes the lock just long enough to push one
+/// write to every still-relevant monitor, then releases it, so other operations aren't blocked
+/// for the fade's whole ~300ms duration. `target_percent_of` is evaluated once per monitor,
+/// against its state just before the fade starts, to decide where that monitor should end up.
+fn fade_for(
+    target: &BacklightTarget,
+    target_percent_of: impl Fn(&dyn BacklightDevice) -> u8,
+) -> anyhow::Result<()> {
+    let generation = FADE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+
+    let targets: Vec<(String, u8)> = MONITORS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|monitor| matches_target(monitor.as_ref(), target))
+        .map(|monitor| (monitor.name(), target_percent_of(monitor.as_ref())))
+        .collect();
+
+    let step_duration = FADE_DURATION / FADE_STEPS;
+    let mut last_error = None;
+
+    for step in 1..=FADE_STEPS {
+        if FADE_GENERATION.load(Ordering::SeqCst) != generation {
+            break;
+        }
+
+        let mut monitors = MONITORS.lock().unwrap();
+
+        for (name, percent) in &targets {
+            let Some(monitor) = monitors.iter_mut().find(|monitor| &monitor.name() == name) else {
+                continue;
+            };
+
+            if let Err(err) = monitor.step_brightness_fade(*percent, step) {
+                log::error!("Unable to fade brightness of {name}: {err}");
+                last_error = Some(err);
+            }
+        }
+
+        drop(monitors);
+
+        if step != FADE_STEPS {
+            thread::sleep(step_duration);
+        }
+    }
+
+    if let Some(err) = last_error {
+        log::info!("Trying to refresh monitors list to fix the error...");
+        refresh_monitors_list();
+        Err(err)
+    } else {
         Ok(())
     }
 }
 
+/// Like `set_brightness_percent_for`, but fades to the target instead of jumping straight there.
+/// Meant for user-facing commands; the periodic auto-adjust nudge uses the immediate variant
+/// since it already runs every few minutes and gains nothing from fading.
+pub(crate) fn fade_brightness_percent_for(percent: u8, target: &BacklightTarget) -> anyhow::Result<()> {
+    fade_for(target, |_| percent)?;
+    log::info!("Brightness is fading to {percent}%");
+    Ok(())
+}
+
 pub(crate) fn increase_brightness_percent(percent: u8) -> anyhow::Result<()> {
+    increase_brightness_percent_for(percent, &BacklightTarget::All)
+}
+
+pub(crate) fn increase_brightness_percent_for(
+    percent: u8,
+    target: &BacklightTarget,
+) -> anyhow::Result<()> {
     let mut last_error = None;
 
-    for monitor in MONITORS.lock().unwrap().iter_mut() {
+    for monitor in MONITORS
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .filter(|monitor| matches_target(monitor.as_ref(), target))
+    {
         let mut new_brightness = monitor.get_brightness() + percent;
 
         if new_brightness > 100 {
@@ -132,20 +267,44 @@ pub(crate) fn increase_brightness_percent(percent: u8) -> anyhow::Result<()> {
         refresh_monitors_list();
         Err(err)
     } else {
-        log::info!("Brightness of all monitors has been set to {percent}%");
+        log::info!("Brightness has been increased by {percent}%");
         Ok(())
     }
 }
 
+/// Like `increase_brightness_percent_for`, but fades to the target instead of jumping straight
+/// there.
+pub(crate) fn fade_increase_brightness_percent_for(
+    percent: u8,
+    target: &BacklightTarget,
+) -> anyhow::Result<()> {
+    fade_for(target, |monitor| (monitor.get_brightness() + percent).min(100))?;
+    log::info!("Brightness is fading up by {percent}%");
+    Ok(())
+}
+
 pub(crate) fn decrease_brightness_percent(percent: u8) -> anyhow::Result<()> {
+    decrease_brightness_percent_for(percent, &BacklightTarget::All)
+}
+
+pub(crate) fn decrease_brightness_percent_for(
+    percent: u8,
+    target: &BacklightTarget,
+) -> anyhow::Result<()> {
     let mut last_error = None;
 
-    for monitor in MONITORS.lock().unwrap().iter_mut() {
+    for monitor in MONITORS
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .filter(|monitor| matches_target(monitor.as_ref(), target))
+    {
         let mut new_brightness = monitor.get_brightness() as i8 - percent as i8;
 
-        // Don't allow setting the brightness to 0 to prevent the monitor from being completely black.
-        if new_brightness < 1 {
-            new_brightness = 1;
+        // Don't drive the monitor below its configured floor (1% by default).
+        let floor = monitor.minimum_brightness() as i8;
+        if new_brightness < floor {
+            new_brightness = floor;
         }
 
         let res = monitor.set_brightness(new_brightness as u8);
@@ -161,15 +320,45 @@ pub(crate) fn decrease_brightness_percent(percent: u8) -> anyhow::Result<()> {
         refresh_monitors_list();
         Err(err)
     } else {
-        log::info!("Brightness of all monitors has been set to {percent}%");
+        log::info!("Brightness has been decreased by {percent}%");
         Ok(())
     }
 }
 
+/// Like `decrease_brightness_percent_for`, but fades to the target instead of jumping straight
+/// there.
+pub(crate) fn fade_decrease_brightness_percent_for(
+    percent: u8,
+    target: &BacklightTarget,
+) -> anyhow::Result<()> {
+    fade_for(target, |monitor| {
+        let mut new_brightness = monitor.get_brightness() as i8 - percent as i8;
+
+        // Don't drive the monitor below its configured floor (1% by default).
+        let floor = monitor.minimum_brightness() as i8;
+        if new_brightness < floor {
+            new_brightness = floor;
+        }
+
+        new_brightness as u8
+    })?;
+    log::info!("Brightness is fading down by {percent}%");
+    Ok(())
+}
+
 pub(crate) fn turn_off() -> anyhow::Result<()> {
+    turn_off_for(&BacklightTarget::All)
+}
+
+pub(crate) fn turn_off_for(target: &BacklightTarget) -> anyhow::Result<()> {
     let mut last_error = None;
 
-    for monitor in MONITORS.lock().unwrap().iter_mut() {
+    for monitor in MONITORS
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .filter(|monitor| matches_target(monitor.as_ref(), target))
+    {
         if let Err(err) = monitor.turn_off() {
             log::error!("Unable to turn OFF monitor: {err}");
             last_error = Some(err);
@@ -181,7 +370,12 @@ pub(crate) fn turn_off() -> anyhow::Result<()> {
         refresh_monitors_list();
 
         last_error = None;
-        for monitor in MONITORS.lock().unwrap().iter_mut() {
+        for monitor in MONITORS
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .filter(|monitor| matches_target(monitor.as_ref(), target))
+        {
             if let Err(err) = monitor.turn_off() {
                 log::error!("Unable to turn OFF monitor: {err}");
                 last_error = Some(err);
@@ -199,9 +393,18 @@ pub(crate) fn turn_off() -> anyhow::Result<()> {
 }
 
 pub(crate) fn turn_on() -> anyhow::Result<()> {
+    turn_on_for(&BacklightTarget::All)
+}
+
+pub(crate) fn turn_on_for(target: &BacklightTarget) -> anyhow::Result<()> {
     let mut last_error = None;
 
-    for monitor in MONITORS.lock().unwrap().iter_mut() {
+    for monitor in MONITORS
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .filter(|monitor| matches_target(monitor.as_ref(), target))
+    {
         if let Err(err) = monitor.turn_on() {
             log::error!("Unable to turn ON monitor: {err}");
             last_error = Some(err);
@@ -213,7 +416,12 @@ pub(crate) fn turn_on() -> anyhow::Result<()> {
         refresh_monitors_list();
 
         last_error = None;
-        for monitor in MONITORS.lock().unwrap().iter_mut() {
+        for monitor in MONITORS
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .filter(|monitor| matches_target(monitor.as_ref(), target))
+        {
             if let Err(err) = monitor.turn_on() {
                 log::error!("Unable to turn ON monitor: {err}");
                 last_error = Some(err);
@@ -232,6 +440,11 @@ pub(crate) fn turn_on() -> anyhow::Result<()> {
 
 pub(crate) fn get_average_brightness() -> u8 {
     let monitors = MONITORS.lock().unwrap();
+
+    if monitors.is_empty() {
+        return 0;
+    }
+
     let mut sum: usize = 0;
 
     for monitor in &*monitors {
@@ -240,3 +453,41 @@ pub(crate) fn get_average_brightness() -> u8 {
 
     (sum / monitors.len()) as u8
 }
+
+pub(crate) fn set_minimum_brightness_for(percent: u8, target: &BacklightTarget) {
+    for monitor in MONITORS
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .filter(|monitor| matches_target(monitor.as_ref(), target))
+    {
+        monitor.set_minimum_brightness(percent);
+    }
+}
+
+/// Re-reads every monitor's state directly from hardware. Called after an inotify event on an
+/// ACPI brightness file, since that event fires on every write to the file -- including our own
+/// -- and not just on a hardware hotkey press. Returns whether any monitor's value genuinely
+/// differed from what we expected, so the caller can tell a real external change from an echo of
+/// a write we just made ourselves.
+pub(crate) fn resync_from_hardware() -> bool {
+    let mut changed = false;
+
+    for monitor in MONITORS.lock().unwrap().iter_mut() {
+        changed |= monitor.resync_from_hardware();
+    }
+
+    changed
+}
+
+pub(crate) fn get_monitors_info() -> Vec<MonitorInfo> {
+    MONITORS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|monitor| MonitorInfo {
+            name: monitor.name(),
+            brightness_percent: monitor.get_brightness(),
+        })
+        .collect()
+}
@@ -0,0 +1,198 @@
+use std::{env, sync::mpsc::Sender, thread, time::Duration};
+
+use backlight_ipc::{BacklightCommand, BacklightMode, BacklightTarget};
+use rumqttc::{Client, Event, MqttOptions, Packet, QoS};
+
+use crate::{current_info, execute_command};
+
+const MQTT_BROKER_URL_VAR: &str = "BACKLIGHTD_MQTT_BROKER_URL";
+const MQTT_COMMAND_TOPIC_VAR: &str = "BACKLIGHTD_MQTT_COMMAND_TOPIC";
+const MQTT_STATUS_TOPIC_VAR: &str = "BACKLIGHTD_MQTT_STATUS_TOPIC";
+const MQTT_USERNAME_VAR: &str = "BACKLIGHTD_MQTT_USERNAME";
+const MQTT_PASSWORD_VAR: &str = "BACKLIGHTD_MQTT_PASSWORD";
+
+const DEFAULT_COMMAND_TOPIC: &str = "backlightd/command";
+const DEFAULT_STATUS_TOPIC: &str = "backlightd/status";
+const DEFAULT_BROKER_PORT: u16 = 1883;
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+struct MqttConfig {
+    broker_url: String,
+    command_topic: String,
+    status_topic: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+fn config_from_env() -> Option<MqttConfig> {
+    let broker_url = env::var(MQTT_BROKER_URL_VAR).ok()?;
+
+    Some(MqttConfig {
+        broker_url,
+        command_topic: env::var(MQTT_COMMAND_TOPIC_VAR)
+            .unwrap_or_else(|_| DEFAULT_COMMAND_TOPIC.to_string()),
+        status_topic: env::var(MQTT_STATUS_TOPIC_VAR)
+            .unwrap_or_else(|_| DEFAULT_STATUS_TOPIC.to_string()),
+        username: env::var(MQTT_USERNAME_VAR).ok(),
+        password: env::var(MQTT_PASSWORD_VAR).ok(),
+    })
+}
+
+/// Mirrors the Unix-socket control surface onto an MQTT broker. Stays inert (parks forever)
+/// unless `BACKLIGHTD_MQTT_BROKER_URL` is set, so `backlightd` behaves exactly as before for
+/// anyone who hasn't configured it -- and keeps parking (instead of returning) if the broker
+/// connection ever drops, since `main`'s supervisory loop treats this thread finishing as fatal.
+pub(crate) fn mqtt_bridge_thread(auto_adjust_sender: Sender<BacklightMode>) -> ! {
+    let Some(config) = config_from_env() else {
+        log::info!("{MQTT_BROKER_URL_VAR} not set, MQTT bridge disabled");
+        park_forever();
+    };
+
+    let (host, port) = config
+        .broker_url
+        .split_once(':')
+        .unwrap_or((config.broker_url.as_str(), ""));
+    let port: u16 = port.parse().unwrap_or(DEFAULT_BROKER_PORT);
+
+    let mut mqtt_options = MqttOptions::new("backlightd", host, port);
+    mqtt_options.set_keep_alive(Duration::from_secs(30));
+
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        mqtt_options.set_credentials(username, password);
+    }
+
+    let (client, mut connection) = Client::new(mqtt_options, 10);
+
+    if let Err(err) = client.subscribe(&config.command_topic, QoS::AtLeastOnce) {
+        log::error!("Unable to subscribe to {}: {err}", config.command_topic);
+        park_forever();
+    }
+
+    log::info!(
+        "MQTT bridge connected to {}, listening on {}",
+        config.broker_url,
+        config.command_topic
+    );
+
+    thread::spawn({
+        let status_client = client.clone();
+        let status_topic = config.status_topic.clone();
+        move || publish_status_loop(status_client, status_topic)
+    });
+
+    for notification in connection.iter() {
+        match notification {
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                let payload = String::from_utf8_lossy(&publish.payload).to_string();
+                handle_mqtt_payload(&payload, &auto_adjust_sender);
+            }
+            Ok(_) => {}
+            Err(err) => log::error!("MQTT connection error: {err}"),
+        }
+    }
+
+    log::error!("MQTT connection closed, bridge is now inert until backlightd is restarted");
+    park_forever();
+}
+
+/// Parks the calling thread forever. Used whenever the MQTT bridge has nothing left to do (not
+/// configured, or its connection is gone), since `main`'s supervisory loop panics the moment any
+/// of its threads finishes.
+fn park_forever() -> ! {
+    loop {
+        thread::sleep(Duration::from_secs(60 * 60));
+    }
+}
+
+fn handle_mqtt_payload(payload: &str, auto_adjust_sender: &Sender<BacklightMode>) {
+    let Some(command) = parse_mqtt_command(payload) else {
+        log::warn!("Ignoring unrecognized MQTT command: {payload:?}");
+        return;
+    };
+
+    if let Err(err) = execute_command(command, auto_adjust_sender) {
+        log::error!("Unable to run MQTT command {payload:?}: {err}");
+    }
+}
+
+/// Parses the small text grammar accepted on the command topic: `"50%"`/`"+10%"`/`"-10%"` for
+/// set/increase/decrease, `"mode:auto"`/`"mode:manual"`/`"mode:adaptive"`, and `"refresh"`.
+fn parse_mqtt_command(payload: &str) -> Option<BacklightCommand> {
+    let payload = payload.trim();
+
+    if payload.eq_ignore_ascii_case("refresh") {
+        return Some(BacklightCommand::Refresh);
+    }
+
+    if let Some(mode) = payload.strip_prefix("mode:") {
+        let mode = match mode.trim().to_lowercase().as_str() {
+            "auto" => BacklightMode::Auto,
+            "manual" => BacklightMode::Manual,
+            "adaptive" => BacklightMode::Adaptive,
+            other => {
+                log::warn!("Unknown mode in MQTT command: {other}");
+                return None;
+            }
+        };
+
+        return Some(BacklightCommand::SetMode(mode));
+    }
+
+    let (sign, digits) = match payload.strip_prefix('+') {
+        Some(digits) => (1, digits),
+        None => match payload.strip_prefix('-') {
+            Some(digits) => (-1, digits),
+            None => (0, payload),
+        },
+    };
+
+    let digits = digits.strip_suffix('%').unwrap_or(digits);
+
+    let percent: u8 = match digits.parse() {
+        Ok(percent) => percent,
+        Err(err) => {
+            log::warn!("Unable to parse MQTT brightness command {payload:?}: {err}");
+            return None;
+        }
+    };
+
+    if percent > 100 {
+        log::warn!("Ignoring out-of-range MQTT brightness command {payload:?}: must be <= 100%");
+        return None;
+    }
+
+    Some(match sign {
+        1 => BacklightCommand::IncreaseBrightness(percent, BacklightTarget::All),
+        -1 => BacklightCommand::DecreaseBrightness(percent, BacklightTarget::All),
+        _ => BacklightCommand::SetBrightness(percent, BacklightTarget::All),
+    })
+}
+
+/// Publishes the current average brightness, per-monitor list and active mode to the status
+/// topic whenever any of them changes, polling at `STATUS_POLL_INTERVAL`.
+fn publish_status_loop(client: Client, status_topic: String) {
+    let mut last_published: Option<String> = None;
+
+    loop {
+        let info = current_info();
+
+        let payload = match serde_json::to_string(&info) {
+            Ok(payload) => payload,
+            Err(err) => {
+                log::error!("Unable to serialize MQTT status: {err}");
+                thread::sleep(STATUS_POLL_INTERVAL);
+                continue;
+            }
+        };
+
+        if last_published.as_deref() != Some(payload.as_str()) {
+            if let Err(err) = client.publish(&status_topic, QoS::AtLeastOnce, true, payload.clone()) {
+                log::error!("Unable to publish MQTT status: {err}");
+            } else {
+                last_published = Some(payload);
+            }
+        }
+
+        thread::sleep(STATUS_POLL_INTERVAL);
+    }
+}